@@ -5,8 +5,9 @@ use vulkano::{
     },
 
     pipeline::{
-        GraphicsPipeline, 
-        viewport::Viewport, 
+        GraphicsPipeline,
+        GraphicsPipelineAbstract,
+        viewport::Viewport,
 		vertex::{
 			SingleBufferDefinition
 		}
@@ -14,6 +15,7 @@ use vulkano::{
 
     device::{
         Device,
+        Queue,
         Features,
         RawDeviceExtensions,
     },
@@ -26,27 +28,38 @@ use vulkano::{
     },
 
     image::{
-        SwapchainImage, 
+        AttachmentImage,
+        Dimensions,
+        ImageLayout,
+        ImageUsage,
+        ImmutableImage,
+        MipmapsCount,
     },
 
+    format::Format,
+
+    sampler::{
+        Sampler,
+        Filter,
+        MipmapMode,
+        SamplerAddressMode,
+    },
+
+    descriptor::descriptor_set::{PersistentDescriptorSet, DescriptorSet},
+
     buffer::{
         BufferUsage,
         CpuAccessibleBuffer,
     },
 
     command_buffer::{
-        AutoCommandBufferBuilder, 
+        AutoCommandBufferBuilder,
         DynamicState
     },
 
     swapchain,
     swapchain::{
-        ColorSpace,
-        FullscreenExclusive,
-        AcquireError, 
-        Swapchain, 
-        SurfaceTransform, 
-        PresentMode, 
+        AcquireError,
         SwapchainCreationError
     },
 
@@ -59,18 +72,23 @@ use vulkano::{
 
 use std::sync::Arc;
 
-use vulkano_win::VkSurfaceBuild; 
+mod renderer;
+use renderer::{SurfaceBinding, SwapchainBinding};
+
+use vulkano_win::VkSurfaceBuild;
 use winit::{
     event_loop::{
         ControlFlow, 
         EventLoop, 
     },
     window::{
-        Window, 
-        WindowBuilder, 
+        WindowBuilder,
     },
     event::{
-        Event, 
+        ElementState,
+        Event,
+        KeyboardInput,
+        VirtualKeyCode,
         WindowEvent
     }
 };
@@ -81,86 +99,89 @@ struct Vertex2dTex {
 	position: [f32; 2],
 	uv: [f32; 2],
 }
-vulkano::impl_vertex!(Vertex2dTex, position, uv); 
+vulkano::impl_vertex!(Vertex2dTex, position, uv);
+
+/// Generates a square RGBA8 checkerboard at runtime, tinted with `color` on its light
+/// squares. Used in place of committed texture assets so the example has no external
+/// files to ship or go stale. This is a deliberate substitute for decoding a PNG with the
+/// `image`/`png` crates: the `image` dependency is still declared (run_headless uses it to
+/// *encode* the offscreen render to PNG), but decoding is unnecessary when there's nothing
+/// checked in to decode.
+fn checkerboard_texture(size: u32, color: [u8; 4]) -> Vec<u8> {
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0 .. size {
+        for x in 0 .. size {
+            let on_light_square = (x / 8 + y / 8) % 2 == 0;
+            data.extend_from_slice(if on_light_square { &color } else { &[255, 255, 255, 255] });
+        }
+    }
+    data
+}
 
 fn main() {
     let instance = {
-        let inst_exts = vulkano_win::required_extensions(); 
+        let inst_exts = vulkano_win::required_extensions();
         Instance::new(None, &inst_exts, None).expect("failed to create instance")
     };
 
-    let physical = PhysicalDevice::enumerate(&instance)
-        .next()
-        .expect("no device available");
-
-    let queue_family = physical
-        .queue_families()
-        .find(|&q| q.supports_graphics())
-        .expect("couldn't find a graphical queue family");
-
-    let (device, mut queues) = {
-        let unraw_dev_exts = vulkano::device::DeviceExtensions {
-            khr_swapchain: true, 
-            .. vulkano::device::DeviceExtensions::none()
+    if std::env::args().any(|arg| arg == "--headless") {
+        let physical = PhysicalDevice::enumerate(&instance)
+            .next()
+            .expect("no device available");
+
+        let queue_family = physical
+            .queue_families()
+            .find(|&q| q.supports_graphics())
+            .expect("couldn't find a graphical queue family");
+
+        let (device, mut queues) = {
+            let unraw_dev_exts = vulkano::device::DeviceExtensions {
+                khr_swapchain: true,
+                .. vulkano::device::DeviceExtensions::none()
+            };
+            let mut dev_exts = RawDeviceExtensions::from(&unraw_dev_exts);
+            dev_exts.insert(std::ffi::CString::new("VK_KHR_storage_buffer_storage_class").unwrap());
+
+            let dev_features = Features {
+                geometry_shader: true,
+                .. Features::none()
+            };
+
+            Device::new(
+                physical,
+                &dev_features,
+                dev_exts,
+                [(queue_family, 0.5)].iter().cloned(),
+            )
+            .expect("failed to create device")
         };
-        let mut dev_exts = RawDeviceExtensions::from(&unraw_dev_exts);
-        dev_exts.insert(std::ffi::CString::new("VK_KHR_storage_buffer_storage_class").unwrap());
-
 
-        let dev_features = Features {
-            geometry_shader: true, 
-            .. Features::none()
-        };
-
-        Device::new(
-            physical,
-            &dev_features, 
-            dev_exts,
-            [(queue_family, 0.5)].iter().cloned(),
-        )
-        .expect("failed to create device")
-    };
-
-    let queue = queues.next().unwrap();
+        let queue = queues.next().unwrap();
+        run_headless(device, queue);
+        return;
+    }
 
-    let event_loop = EventLoop::new(); 
+    let event_loop = EventLoop::new();
 
     let surface = WindowBuilder::new().build_vk_surface(&event_loop, instance.clone()).unwrap();
 
-    let (mut swapchain, images) = {
-        let caps = surface.capabilities(physical) 
-            .expect("failed to get surface capabilities"); 
-        let usage = caps.supported_usage_flags; 
-        let alpha = caps.supported_composite_alpha.iter().next().unwrap(); 
-        let format = caps.supported_formats[0].0;
-
-        let dim: [u32; 2] = surface.window().inner_size().into();
-
-        Swapchain::new(
-            device.clone(), 
-            surface.clone(), 
-            caps.min_image_count, format, dim, 1, usage, &queue, 
-            SurfaceTransform::Identity, alpha, PresentMode::Fifo, FullscreenExclusive::Default, false, ColorSpace::SrgbNonLinear)
-        .expect("failed to create swapchain")
+    let surface_binding = SurfaceBinding::new(instance.clone(), &surface);
+    let device = surface_binding.device.clone();
+    let queue = surface_binding.graphics_queue.clone();
+    let present_queue = surface_binding.present_queue.clone();
+
+    let mut dynamic_state = DynamicState {
+        line_width: None,
+        viewports: None,
+        scissors: None,
+        compare_mask: None,
+        write_mask: None,
+        reference: None
     };
 
-    let render_pass = Arc::new(vulkano::single_pass_renderpass!(
-        device.clone(),
-        attachments: {
-            color: {
-                load: Clear,
-                store: Store,
-                format: swapchain.format(),
-                samples: 1,
-            }
-        },
-        pass: {
-            color: [color],
-            depth_stencil: {}
-        }
-    ).unwrap());
+    let mut swapchain_binding = SwapchainBinding::new(&surface_binding, surface.clone(), &mut dynamic_state);
 
-    mod vs { 
+    mod vs {
         vulkano_shaders::shader!{
             ty: "vertex", 
             path: "./src/vs.glsl"
@@ -178,7 +199,29 @@ fn main() {
     }
     #[allow(dead_code)] // Used to force recompilation of shader change
     const FS: &str = include_str!("./fs.glsl");
-    let fs = fs::Shader::load(device.clone()).unwrap(); 
+    let fs = fs::Shader::load(device.clone()).unwrap();
+
+    let skybox_mode = std::env::args().any(|arg| arg == "--skybox");
+
+    mod vs_cube {
+        vulkano_shaders::shader!{
+            ty: "vertex",
+            path: "./src/vs_cube.glsl"
+        }
+    }
+    #[allow(dead_code)] // Used to force recompilation of shader change
+    const VS_CUBE: &str = include_str!("./vs_cube.glsl");
+    let vs_cube = vs_cube::Shader::load(device.clone()).unwrap();
+
+    mod fs_cube {
+        vulkano_shaders::shader!{
+            ty: "fragment",
+            path: "./src/fs_cube.glsl"
+        }
+    }
+    #[allow(dead_code)] // Used to force recompilation of shader change
+    const FS_CUBE: &str = include_str!("./fs_cube.glsl");
+    let fs_cube = fs_cube::Shader::load(device.clone()).unwrap();
 
     let pipeline = Arc::new(GraphicsPipeline::start()
         .vertex_input(SingleBufferDefinition::<Vertex2dTex>::new())
@@ -187,11 +230,174 @@ fn main() {
         .viewports_dynamic_scissors_irrelevant(1)
         .fragment_shader(fs.main_entry_point(), ())
         .blend_alpha_blending()
-        .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
+        .depth_stencil_simple_depth()
+        .render_pass(Subpass::from(swapchain_binding.render_pass.clone(), 0).unwrap())
         .build(device.clone())
         .unwrap()
     );
 
+    const TEXTURE_SIZE: u32 = 64;
+    const TEXTURE_LAYER_COLORS: [[u8; 4]; 3] = [
+        [220, 60, 60, 255],
+        [60, 200, 90, 255],
+        [70, 110, 230, 255],
+    ];
+
+    let texture = {
+        let mut image_data = Vec::new();
+
+        for color in TEXTURE_LAYER_COLORS.iter() {
+            image_data.extend(checkerboard_texture(TEXTURE_SIZE, *color));
+        }
+
+        let staging_buffer = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::transfer_source(),
+            false,
+            image_data.into_iter(),
+        ).unwrap();
+
+        let dimensions = Dimensions::Dim2dArray {
+            width: TEXTURE_SIZE,
+            height: TEXTURE_SIZE,
+            array_layers: TEXTURE_LAYER_COLORS.len() as u32,
+        };
+        let (image, init) = ImmutableImage::uninitialized(
+            device.clone(),
+            dimensions,
+            Format::R8G8B8A8Srgb,
+            MipmapsCount::One,
+            ImageUsage {
+                transfer_destination: true,
+                sampled: true,
+                .. ImageUsage::none()
+            },
+            ImageLayout::ShaderReadOnlyOptimal,
+            Some(queue.family()),
+        ).unwrap();
+
+        let mut cbb = AutoCommandBufferBuilder::primary_one_time_submit(device.clone(), queue.family()).unwrap();
+        cbb.copy_buffer_to_image(staging_buffer, init).unwrap();
+        let command_buffer = cbb.build().unwrap();
+
+        let future = sync::now(device.clone())
+            .then_execute(queue.clone(), command_buffer).unwrap()
+            .then_signal_fence_and_flush().unwrap();
+        future.wait(None).unwrap();
+
+        image
+    };
+
+    let sampler = Sampler::new(
+        device.clone(),
+        Filter::Linear,
+        Filter::Linear,
+        MipmapMode::Nearest,
+        SamplerAddressMode::Repeat,
+        SamplerAddressMode::Repeat,
+        SamplerAddressMode::Repeat,
+        0.0, 1.0, 0.0, 0.0,
+    ).unwrap();
+
+    let descriptor_set = Arc::new(
+        PersistentDescriptorSet::start(pipeline.descriptor_set_layout(0).unwrap().clone())
+            .add_sampled_image(texture.clone(), sampler.clone()).unwrap()
+            .build().unwrap()
+    );
+
+    const CUBE_FACE_SIZE: u32 = 64;
+    const CUBE_FACE_COLORS: [[u8; 4]; 6] = [
+        [230, 60, 60, 255],   // +X
+        [60, 230, 230, 255],  // -X
+        [60, 230, 60, 255],   // +Y
+        [230, 60, 230, 255],  // -Y
+        [60, 60, 230, 255],   // +Z
+        [230, 230, 60, 255],  // -Z
+    ];
+
+    enum ModeResources {
+        Textured {
+            pipeline: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+            descriptor_set: Arc<dyn DescriptorSet + Send + Sync>,
+        },
+        Cubemap {
+            pipeline: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+            descriptor_set: Arc<dyn DescriptorSet + Send + Sync>,
+        },
+    }
+
+    let mode_resources = if skybox_mode {
+        let cube_pipeline = Arc::new(GraphicsPipeline::start()
+            .vertex_input(SingleBufferDefinition::<Vertex2dTex>::new())
+            .vertex_shader(vs_cube.main_entry_point(), ())
+            .triangle_strip()
+            .viewports_dynamic_scissors_irrelevant(1)
+            .fragment_shader(fs_cube.main_entry_point(), ())
+            .blend_alpha_blending()
+            .depth_stencil_simple_depth()
+            .render_pass(Subpass::from(swapchain_binding.render_pass.clone(), 0).unwrap())
+            .build(device.clone())
+            .unwrap()
+        );
+
+        let cube_texture = {
+            let mut image_data = Vec::new();
+
+            for color in CUBE_FACE_COLORS.iter() {
+                image_data.extend(checkerboard_texture(CUBE_FACE_SIZE, *color));
+            }
+
+            let staging_buffer = CpuAccessibleBuffer::from_iter(
+                device.clone(),
+                BufferUsage::transfer_source(),
+                false,
+                image_data.into_iter(),
+            ).unwrap();
+
+            let dimensions = Dimensions::Cubemap { size: CUBE_FACE_SIZE };
+            let (image, init) = ImmutableImage::uninitialized(
+                device.clone(),
+                dimensions,
+                Format::R8G8B8A8Srgb,
+                MipmapsCount::One,
+                ImageUsage {
+                    transfer_destination: true,
+                    sampled: true,
+                    .. ImageUsage::none()
+                },
+                ImageLayout::ShaderReadOnlyOptimal,
+                Some(queue.family()),
+            ).unwrap();
+
+            let mut cbb = AutoCommandBufferBuilder::primary_one_time_submit(device.clone(), queue.family()).unwrap();
+            cbb.copy_buffer_to_image(staging_buffer, init).unwrap();
+            let command_buffer = cbb.build().unwrap();
+
+            let future = sync::now(device.clone())
+                .then_execute(queue.clone(), command_buffer).unwrap()
+                .then_signal_fence_and_flush().unwrap();
+            future.wait(None).unwrap();
+
+            image
+        };
+
+        let cube_descriptor_set = Arc::new(
+            PersistentDescriptorSet::start(cube_pipeline.descriptor_set_layout(0).unwrap().clone())
+                .add_sampled_image(cube_texture.clone(), sampler.clone()).unwrap()
+                .build().unwrap()
+        );
+
+        ModeResources::Cubemap {
+            pipeline: cube_pipeline as Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+            descriptor_set: cube_descriptor_set as Arc<dyn DescriptorSet + Send + Sync>,
+        }
+    } else {
+        ModeResources::Textured {
+            pipeline: pipeline.clone() as Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+            descriptor_set: descriptor_set.clone() as Arc<dyn DescriptorSet + Send + Sync>,
+        }
+    };
+
     let vertex_buffer = {
         CpuAccessibleBuffer::from_iter(
             device.clone(), 
@@ -206,42 +412,53 @@ fn main() {
         ).unwrap();
     };
 
-    let mut dynamic_state = DynamicState { 
-        line_width: None, 
-        viewports: None, 
-        scissors: None, 
-        compare_mask: None, 
-        write_mask: None, 
-        reference: None 
-    }; 
-    
-    let mut framebuffers = window_size_dependent_setup(&images, render_pass.clone(), &mut dynamic_state); 
+    let mut recreate_swapchain = false;
 
-    let mut recreate_swapchain = false; 
+    // A ring of in-flight frame futures, one per concurrently-submitted frame. Each slot's
+    // future carries the acquire semaphore, render-finished semaphore and completion fence
+    // that vulkano's GpuFuture chain manages internally. When a slot comes round again its
+    // old future is only cleaned up (cleanup_finished) and joined into the new submission,
+    // which orders the new frame after it on the GPU; the CPU does not wait on the slot's
+    // fence at any point.
+    const MAX_FRAMES_IN_FLIGHT: usize = 2;
+    let mut frames_in_flight: Vec<Option<Box<dyn GpuFuture>>> = (0 .. MAX_FRAMES_IN_FLIGHT)
+        .map(|_| None)
+        .collect();
+    let mut current_frame = 0;
 
-    let mut previous_frame_end = Some(Box::new(sync::now(device.clone())) as Box<dyn GpuFuture>); 
+    // Which stacked texture layer the array-mode quad samples; advanced on demand by
+    // pressing Space rather than cycling automatically, so it doesn't flicker.
+    let mut current_layer: i32 = 0;
 
     event_loop.run(move |event, _, control_flow| {
         match event {
             Event::WindowEvent { event: WindowEvent::CloseRequested, .. } => *control_flow = ControlFlow::Exit,
             Event::WindowEvent { event: WindowEvent::Resized(_), .. } => recreate_swapchain = true,
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput {
+                    input: KeyboardInput { state: ElementState::Pressed, virtual_keycode: Some(VirtualKeyCode::Space), .. },
+                    ..
+                },
+                ..
+            } => {
+                current_layer = (current_layer + 1) % TEXTURE_LAYER_COLORS.len() as i32;
+            },
             Event::RedrawEventsCleared => {
-                previous_frame_end.as_mut().unwrap().cleanup_finished(); 
+                if let Some(previous_frame_end) = frames_in_flight[current_frame].as_mut() {
+                    previous_frame_end.cleanup_finished();
+                }
 
                 if recreate_swapchain {
                     let dim: [u32; 2] = surface.window().inner_size().into();
-                    let (new_swapchain, new_images) = match swapchain.recreate_with_dimensions(dim) {
-                        Ok(r) => r, 
-                        Err(SwapchainCreationError::UnsupportedDimensions) => return, 
+                    match swapchain_binding.recreate(dim, &mut dynamic_state) {
+                        Ok(()) => {},
+                        Err(SwapchainCreationError::UnsupportedDimensions) => return,
                         Err(err) => panic!("failed to recreate swapchain {:?}", err)
-                    }; 
-
-                    swapchain = new_swapchain; 
-                    framebuffers = window_size_dependent_setup(&new_images, render_pass.clone(), &mut dynamic_state); 
-                    recreate_swapchain = false; 
+                    };
+                    recreate_swapchain = false;
                 }
 
-                let (image_num, suboptimal, acquire_future) = match swapchain::acquire_next_image(swapchain.clone(), None){
+                let (image_num, suboptimal, acquire_future) = match swapchain::acquire_next_image(swapchain_binding.swapchain.clone(), None){
                     Ok(r) => r, 
                     Err(AcquireError::OutOfDate) => {
                         recreate_swapchain = true; 
@@ -254,74 +471,262 @@ fn main() {
                     recreate_swapchain = true; 
                 }
 
-                let clear_values = vec!([1.0, 1.0, 1.0, 1.0].into()); 
-                let command_buffer = AutoCommandBufferBuilder::primary_one_time_submit(
-                    device.clone(), 
+                let clear_values = vec!([1.0, 1.0, 1.0, 1.0].into(), 1.0f32.into());
+                let command_buffer_builder = AutoCommandBufferBuilder::primary_one_time_submit(
+                    device.clone(),
                     queue.family()
                 )
                     .unwrap()
-                    .begin_render_pass(framebuffers[image_num].clone(), false, clear_values)
-                    .unwrap()
-                    .draw(
-                        pipeline.clone(), 
-                        &dynamic_state, 
-                        vertex_buffer.clone(), 
-                        (), 
-                        ()
-                    )
-                    .unwrap()
+                    .begin_render_pass(swapchain_binding.framebuffers[image_num].clone(), false, clear_values)
+                    .unwrap();
+
+                let command_buffer_builder = match &mode_resources {
+                    ModeResources::Textured { pipeline, descriptor_set } => {
+                        let push_constants = fs::ty::PushConstants { layer: current_layer };
+
+                        command_buffer_builder.draw(
+                            pipeline.clone(),
+                            &dynamic_state,
+                            vertex_buffer.clone(),
+                            descriptor_set.clone(),
+                            push_constants
+                        ).unwrap()
+                    }
+                    ModeResources::Cubemap { pipeline, descriptor_set } => {
+                        command_buffer_builder.draw(
+                            pipeline.clone(),
+                            &dynamic_state,
+                            vertex_buffer.clone(),
+                            descriptor_set.clone(),
+                            ()
+                        ).unwrap()
+                    }
+                };
+
+                let command_buffer = command_buffer_builder
                     .end_render_pass()
                     .unwrap()
                     .build()
                     .unwrap();
 
-                let future = previous_frame_end.take().unwrap()
+                let previous_frame_end = frames_in_flight[current_frame]
+                    .take()
+                    .unwrap_or_else(|| Box::new(sync::now(device.clone())) as Box<dyn GpuFuture>);
+
+                let future = previous_frame_end
                     .join(acquire_future)
                     .then_execute(queue.clone(), command_buffer).unwrap()
-                    .then_swapchain_present(queue.clone(), swapchain.clone(), image_num) 
-                    .then_signal_fence_and_flush(); 
+                    .then_swapchain_present(present_queue.clone(), swapchain_binding.swapchain.clone(), image_num)
+                    .then_signal_fence_and_flush();
 
-                match future {
-                    Ok(future) => {
-                        future.wait(None).unwrap(); 
-                        previous_frame_end = Some(Box::new(future) as Box<_>);
-                    }
+                frames_in_flight[current_frame] = match future {
+                    Ok(future) => Some(Box::new(future) as Box<_>),
                     Err(FlushError::OutOfDate) => {
-                        recreate_swapchain = true; 
-                        previous_frame_end = Some(Box::new(sync::now(device.clone())) as Box<_>);
+                        recreate_swapchain = true;
+                        Some(Box::new(sync::now(device.clone())) as Box<_>)
                     }
                     Err(e) => {
                         println!("{:?}", e);
-                        previous_frame_end = Some(Box::new(sync::now(device.clone())) as Box<_>); 
+                        Some(Box::new(sync::now(device.clone())) as Box<_>)
                     }
-                }
+                };
+
+                current_frame = (current_frame + 1) % MAX_FRAMES_IN_FLIGHT;
             },
             _ => ()
         }
     });
 }
 
-fn window_size_dependent_setup(
-    images: &[Arc<SwapchainImage<Window>>], 
-    render_pass: Arc<dyn RenderPassAbstract + Send + Sync>, 
-    dynamic_state: &mut DynamicState
-) -> Vec<Arc<dyn FramebufferAbstract + Send + Sync>> {
-    let dimensions = images[0].dimensions(); 
-
-    let viewport = Viewport {
-        origin: [0.0, 0.0],
-        dimensions: [dimensions[0] as f32, dimensions[1] as f32], 
-        depth_range: 0.0 .. 1.0, 
-    }; 
-
-    dynamic_state.viewports = Some(vec!(viewport)); 
-
-    images.iter().map(|image| {
-        Arc::new(
-            Framebuffer::start(render_pass.clone())
-                .add(image.clone()).unwrap()
-                .build().unwrap()
-        ) as Arc<dyn FramebufferAbstract + Send + Sync>
-    }).collect::<Vec<_>>()
+const HEADLESS_DIMENSIONS: [u32; 2] = [256, 256];
+const HEADLESS_OUTPUT_PATH: &str = "./headless_output.png";
+
+fn run_headless(device: Arc<Device>, queue: Arc<Queue>) {
+    let color_image = AttachmentImage::with_usage(
+        device.clone(),
+        HEADLESS_DIMENSIONS,
+        Format::R8G8B8A8Srgb,
+        ImageUsage {
+            transfer_source: true,
+            .. ImageUsage::color_attachment()
+        },
+    ).unwrap();
+
+    let render_pass = Arc::new(vulkano::single_pass_renderpass!(
+        device.clone(),
+        attachments: {
+            color: {
+                load: Clear,
+                store: Store,
+                format: Format::R8G8B8A8Srgb,
+                samples: 1,
+            }
+        },
+        pass: {
+            color: [color],
+            depth_stencil: {}
+        }
+    ).unwrap());
+
+    let framebuffer = Arc::new(
+        Framebuffer::start(render_pass.clone() as Arc<dyn RenderPassAbstract + Send + Sync>)
+            .add(color_image.clone()).unwrap()
+            .build().unwrap()
+    );
+
+    mod vs_headless {
+        vulkano_shaders::shader!{
+            ty: "vertex",
+            path: "./src/vs.glsl"
+        }
+    }
+    let vs = vs_headless::Shader::load(device.clone()).unwrap();
+
+    mod fs_headless {
+        vulkano_shaders::shader!{
+            ty: "fragment",
+            path: "./src/fs_headless.glsl"
+        }
+    }
+    let fs = fs_headless::Shader::load(device.clone()).unwrap();
+
+    let pipeline = Arc::new(GraphicsPipeline::start()
+        .vertex_input(SingleBufferDefinition::<Vertex2dTex>::new())
+        .vertex_shader(vs.main_entry_point(), ())
+        .triangle_strip()
+        .viewports_dynamic_scissors_irrelevant(1)
+        .fragment_shader(fs.main_entry_point(), ())
+        .blend_alpha_blending()
+        .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
+        .build(device.clone())
+        .unwrap()
+    );
+
+    const HEADLESS_TEXTURE_SIZE: u32 = 64;
+    const HEADLESS_TEXTURE_COLOR: [u8; 4] = [220, 60, 60, 255];
+
+    let texture = {
+        let image_data = checkerboard_texture(HEADLESS_TEXTURE_SIZE, HEADLESS_TEXTURE_COLOR);
+
+        let staging_buffer = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::transfer_source(),
+            false,
+            image_data.into_iter(),
+        ).unwrap();
+
+        let dimensions = Dimensions::Dim2d {
+            width: HEADLESS_TEXTURE_SIZE,
+            height: HEADLESS_TEXTURE_SIZE,
+        };
+        let (image, init) = ImmutableImage::uninitialized(
+            device.clone(),
+            dimensions,
+            Format::R8G8B8A8Srgb,
+            MipmapsCount::One,
+            ImageUsage {
+                transfer_destination: true,
+                sampled: true,
+                .. ImageUsage::none()
+            },
+            ImageLayout::ShaderReadOnlyOptimal,
+            Some(queue.family()),
+        ).unwrap();
+
+        let mut cbb = AutoCommandBufferBuilder::primary_one_time_submit(device.clone(), queue.family()).unwrap();
+        cbb.copy_buffer_to_image(staging_buffer, init).unwrap();
+        let command_buffer = cbb.build().unwrap();
+
+        let future = sync::now(device.clone())
+            .then_execute(queue.clone(), command_buffer).unwrap()
+            .then_signal_fence_and_flush().unwrap();
+        future.wait(None).unwrap();
+
+        image
+    };
+
+    let sampler = Sampler::new(
+        device.clone(),
+        Filter::Linear,
+        Filter::Linear,
+        MipmapMode::Nearest,
+        SamplerAddressMode::Repeat,
+        SamplerAddressMode::Repeat,
+        SamplerAddressMode::Repeat,
+        0.0, 1.0, 0.0, 0.0,
+    ).unwrap();
+
+    let descriptor_set = Arc::new(
+        PersistentDescriptorSet::start(pipeline.descriptor_set_layout(0).unwrap().clone())
+            .add_sampled_image(texture.clone(), sampler.clone()).unwrap()
+            .build().unwrap()
+    );
+
+    let vertex_buffer = CpuAccessibleBuffer::from_iter(
+        device.clone(),
+        BufferUsage::all(),
+        false,
+        [
+            Vertex2dTex { position: [-0.5, -0.5], uv: [0.0, 0.0] },
+            Vertex2dTex { position: [-0.5,  0.5], uv: [0.0, 1.0] },
+            Vertex2dTex { position: [ 0.5, -0.5], uv: [1.0, 0.0] },
+            Vertex2dTex { position: [ 0.5,  0.5], uv: [1.0, 1.0] },
+        ].iter().cloned()
+    ).unwrap();
+
+    let dynamic_state = DynamicState {
+        line_width: None,
+        viewports: Some(vec![Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [HEADLESS_DIMENSIONS[0] as f32, HEADLESS_DIMENSIONS[1] as f32],
+            depth_range: 0.0 .. 1.0,
+        }]),
+        scissors: None,
+        compare_mask: None,
+        write_mask: None,
+        reference: None,
+    };
+
+    let output_buffer = CpuAccessibleBuffer::from_iter(
+        device.clone(),
+        BufferUsage::transfer_destination(),
+        false,
+        (0 .. HEADLESS_DIMENSIONS[0] * HEADLESS_DIMENSIONS[1] * 4).map(|_| 0u8),
+    ).unwrap();
+
+    let clear_values = vec!([0.0, 0.0, 0.0, 1.0].into());
+    let command_buffer = AutoCommandBufferBuilder::primary_one_time_submit(device.clone(), queue.family())
+        .unwrap()
+        .begin_render_pass(framebuffer.clone(), false, clear_values)
+        .unwrap()
+        .draw(
+            pipeline.clone(),
+            &dynamic_state,
+            vertex_buffer.clone(),
+            descriptor_set.clone(),
+            ()
+        )
+        .unwrap()
+        .end_render_pass()
+        .unwrap()
+        .copy_image_to_buffer(color_image.clone(), output_buffer.clone())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let future = sync::now(device.clone())
+        .then_execute(queue.clone(), command_buffer).unwrap()
+        .then_signal_fence_and_flush().unwrap();
+    future.wait(None).unwrap();
+
+    let buffer_content = output_buffer.read().unwrap();
+    let output_image = image::RgbaImage::from_raw(
+        HEADLESS_DIMENSIONS[0],
+        HEADLESS_DIMENSIONS[1],
+        buffer_content.to_vec(),
+    ).expect("output buffer had unexpected size");
+    output_image.save(HEADLESS_OUTPUT_PATH).expect("failed to save headless render output");
+
+    println!("headless render written to {}", HEADLESS_OUTPUT_PATH);
 }
 
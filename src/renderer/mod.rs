@@ -0,0 +1,5 @@
+mod surface_binding;
+mod swapchain_binding;
+
+pub use surface_binding::SurfaceBinding;
+pub use swapchain_binding::SwapchainBinding;
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use vulkano::device::{Device, DeviceExtensions, Features, Queue, RawDeviceExtensions};
+use vulkano::instance::{Instance, PhysicalDevice, PhysicalDeviceType};
+use vulkano::swapchain::Surface;
+
+use winit::window::Window;
+
+/// Owns the instance-derived logical device and queues for a particular surface, having
+/// picked the physical device that best supports presenting to it rather than grabbing
+/// the first one enumerated.
+pub struct SurfaceBinding {
+    pub instance: Arc<Instance>,
+    pub physical_device_index: usize,
+    pub device: Arc<Device>,
+    pub graphics_queue: Arc<Queue>,
+    pub present_queue: Arc<Queue>,
+}
+
+impl SurfaceBinding {
+    pub fn new(instance: Arc<Instance>, surface: &Arc<Surface<Window>>) -> SurfaceBinding {
+        let (physical_device_index, graphics_family_id, present_family_id) =
+            pick_physical_device(&instance, surface);
+
+        let physical = PhysicalDevice::from_index(&instance, physical_device_index).unwrap();
+        let graphics_family = physical.queue_families().nth(graphics_family_id).unwrap();
+        let present_family = physical.queue_families().nth(present_family_id).unwrap();
+
+        let unraw_dev_exts = DeviceExtensions {
+            khr_swapchain: true,
+            .. DeviceExtensions::none()
+        };
+        let mut dev_exts = RawDeviceExtensions::from(&unraw_dev_exts);
+        dev_exts.insert(std::ffi::CString::new("VK_KHR_storage_buffer_storage_class").unwrap());
+
+        let dev_features = Features {
+            geometry_shader: true,
+            .. Features::none()
+        };
+
+        let queue_requests = if graphics_family_id == present_family_id {
+            vec![(graphics_family, 0.5)]
+        } else {
+            vec![(graphics_family, 0.5), (present_family, 0.5)]
+        };
+
+        let (device, mut queues) = Device::new(
+            physical,
+            &dev_features,
+            dev_exts,
+            queue_requests.into_iter(),
+        ).expect("failed to create device");
+
+        let graphics_queue = queues.next().unwrap();
+        let present_queue = if graphics_family_id == present_family_id {
+            graphics_queue.clone()
+        } else {
+            queues.next().unwrap()
+        };
+
+        SurfaceBinding {
+            instance,
+            physical_device_index,
+            device,
+            graphics_queue,
+            present_queue,
+        }
+    }
+
+    pub fn physical_device(&self) -> PhysicalDevice {
+        PhysicalDevice::from_index(&self.instance, self.physical_device_index).unwrap()
+    }
+}
+
+/// Scores every physical device that has both a graphics-capable queue family and a queue
+/// family able to present to `surface`, and picks the highest-scoring one. Discrete GPUs are
+/// preferred over integrated/virtual ones, which is a better default than taking whatever
+/// `PhysicalDevice::enumerate` yields first.
+fn pick_physical_device(instance: &Arc<Instance>, surface: &Arc<Surface<Window>>) -> (usize, usize, usize) {
+    PhysicalDevice::enumerate(instance)
+        .filter_map(|physical| {
+            let graphics_family_id = physical.queue_families()
+                .position(|q| q.supports_graphics())?;
+            let present_family_id = physical.queue_families()
+                .position(|q| surface.is_supported(q).unwrap_or(false))?;
+
+            let score = match physical.ty() {
+                PhysicalDeviceType::DiscreteGpu => 3,
+                PhysicalDeviceType::IntegratedGpu => 2,
+                PhysicalDeviceType::VirtualGpu => 1,
+                _ => 0,
+            };
+
+            Some((score, physical.index(), graphics_family_id, present_family_id))
+        })
+        .max_by_key(|&(score, ..)| score)
+        .map(|(_, index, graphics_family_id, present_family_id)| (index, graphics_family_id, present_family_id))
+        .expect("no physical device supports both graphics and presenting to this surface")
+}
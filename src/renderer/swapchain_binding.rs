@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use vulkano::command_buffer::DynamicState;
+use vulkano::device::Device;
+use vulkano::format::Format;
+use vulkano::framebuffer::{Framebuffer, FramebufferAbstract, RenderPassAbstract};
+use vulkano::image::{AttachmentImage, SwapchainImage};
+use vulkano::pipeline::viewport::Viewport;
+use vulkano::swapchain::{
+    ColorSpace, FullscreenExclusive, PresentMode, Surface, SurfaceTransform, Swapchain,
+    SwapchainCreationError,
+};
+
+use winit::window::Window;
+
+use super::SurfaceBinding;
+
+const DEPTH_FORMAT: Format = Format::D16Unorm;
+
+/// Owns the swapchain, its images, the depth buffers, the render pass they're presented
+/// through, and the framebuffers derived from them. Each swapchain image gets its own depth
+/// buffer, since multiple images can be targeted by concurrently in-flight frames and a
+/// single shared depth attachment would serialize (or race) across them. `recreate` rebuilds
+/// swapchain, depth buffers and framebuffers together, since all three must stay in sync on
+/// resize.
+pub struct SwapchainBinding {
+    pub swapchain: Arc<Swapchain<Window>>,
+    pub images: Vec<Arc<SwapchainImage<Window>>>,
+    pub render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+    pub depth_buffers: Vec<Arc<AttachmentImage>>,
+    pub framebuffers: Vec<Arc<dyn FramebufferAbstract + Send + Sync>>,
+}
+
+impl SwapchainBinding {
+    pub fn new(
+        surface_binding: &SurfaceBinding,
+        surface: Arc<Surface<Window>>,
+        dynamic_state: &mut DynamicState,
+    ) -> SwapchainBinding {
+        let device = surface_binding.device.clone();
+
+        let (swapchain, images) = {
+            let caps = surface.capabilities(surface_binding.physical_device())
+                .expect("failed to get surface capabilities");
+            let usage = caps.supported_usage_flags;
+            let alpha = caps.supported_composite_alpha.iter().next().unwrap();
+            let format = caps.supported_formats[0].0;
+
+            let dim: [u32; 2] = surface.window().inner_size().into();
+
+            Swapchain::new(
+                device.clone(),
+                surface.clone(),
+                caps.min_image_count, format, dim, 1, usage, &surface_binding.present_queue,
+                SurfaceTransform::Identity, alpha, PresentMode::Fifo, FullscreenExclusive::Default, false, ColorSpace::SrgbNonLinear)
+                .expect("failed to create swapchain")
+        };
+
+        let render_pass = build_render_pass(device.clone(), swapchain.format());
+        let depth_buffers = build_depth_buffers(device, &images);
+        let framebuffers = window_size_dependent_setup(&images, render_pass.clone(), &depth_buffers, dynamic_state);
+
+        SwapchainBinding { swapchain, images, render_pass, depth_buffers, framebuffers }
+    }
+
+    pub fn recreate(
+        &mut self,
+        dimensions: [u32; 2],
+        dynamic_state: &mut DynamicState,
+    ) -> Result<(), SwapchainCreationError> {
+        let (new_swapchain, new_images) = self.swapchain.recreate_with_dimensions(dimensions)?;
+
+        self.swapchain = new_swapchain;
+        self.images = new_images;
+        self.depth_buffers = build_depth_buffers(self.swapchain.device().clone(), &self.images);
+        self.framebuffers = window_size_dependent_setup(
+            &self.images,
+            self.render_pass.clone(),
+            &self.depth_buffers,
+            dynamic_state,
+        );
+
+        Ok(())
+    }
+}
+
+fn build_render_pass(device: Arc<Device>, format: Format) -> Arc<dyn RenderPassAbstract + Send + Sync> {
+    Arc::new(vulkano::single_pass_renderpass!(
+        device,
+        attachments: {
+            color: {
+                load: Clear,
+                store: Store,
+                format: format,
+                samples: 1,
+            },
+            depth: {
+                load: Clear,
+                store: DontCare,
+                format: DEPTH_FORMAT,
+                samples: 1,
+            }
+        },
+        pass: {
+            color: [color],
+            depth_stencil: {depth}
+        }
+    ).unwrap())
+}
+
+fn build_depth_buffers(device: Arc<Device>, images: &[Arc<SwapchainImage<Window>>]) -> Vec<Arc<AttachmentImage>> {
+    let dimensions = images[0].dimensions();
+    images.iter()
+        .map(|_| AttachmentImage::transient(device.clone(), [dimensions[0], dimensions[1]], DEPTH_FORMAT).unwrap())
+        .collect()
+}
+
+fn window_size_dependent_setup(
+    images: &[Arc<SwapchainImage<Window>>],
+    render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+    depth_buffers: &[Arc<AttachmentImage>],
+    dynamic_state: &mut DynamicState,
+) -> Vec<Arc<dyn FramebufferAbstract + Send + Sync>> {
+    let dimensions = images[0].dimensions();
+
+    let viewport = Viewport {
+        origin: [0.0, 0.0],
+        dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+        depth_range: 0.0 .. 1.0,
+    };
+
+    dynamic_state.viewports = Some(vec!(viewport));
+
+    images.iter().zip(depth_buffers.iter()).map(|(image, depth_buffer)| {
+        Arc::new(
+            Framebuffer::start(render_pass.clone())
+                .add(image.clone()).unwrap()
+                .add(depth_buffer.clone()).unwrap()
+                .build().unwrap()
+        ) as Arc<dyn FramebufferAbstract + Send + Sync>
+    }).collect::<Vec<_>>()
+}